@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// The level of access granted to a client for a given document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Authorization {
+    ReadOnly,
+    Full,
+}
+
+impl Default for Authorization {
+    fn default() -> Self {
+        Authorization::Full
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocCreationRequest {
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub authorization: Option<Authorization>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewDocResponse {
+    pub doc: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClientToken {
+    pub url: String,
+    pub doc: String,
+    pub token: Option<String>,
+    pub authorization: Authorization,
+    /// Absolute unix millisecond timestamp at which `token` expires, if any.
+    pub expires_at: Option<u64>,
+}
+
+const VALID_DOC_NAME_CHARS: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-";
+
+pub fn validate_doc_name(doc_name: &str) -> bool {
+    !doc_name.is_empty()
+        && doc_name
+            .chars()
+            .all(|c| VALID_DOC_NAME_CHARS.contains(c))
+}
@@ -0,0 +1,140 @@
+use crate::api_types::Authorization;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const DEFAULT_EXPIRATION_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug)]
+pub enum AuthError {
+    InvalidToken,
+    Expired,
+    InvalidResource,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DocPayload {
+    doc_id: String,
+    authorization: Authorization,
+    expiration_millis: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerPayload {
+    expiration_millis: u64,
+}
+
+pub struct Authenticator {
+    private_key: Vec<u8>,
+}
+
+impl Authenticator {
+    pub fn new(private_key: &str) -> Self {
+        Self {
+            private_key: private_key.as_bytes().to_vec(),
+        }
+    }
+
+    fn sign(&self, payload: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.private_key).expect("key of any length");
+        mac.update(payload);
+        let signature = mac.finalize().into_bytes();
+        format!(
+            "{}.{}",
+            base64::encode(payload),
+            base64::encode(signature)
+        )
+    }
+
+    fn verify(&self, token: &str) -> Result<Vec<u8>, AuthError> {
+        let (payload_b64, sig_b64) = token.split_once('.').ok_or(AuthError::InvalidToken)?;
+        let payload = base64::decode(payload_b64).map_err(|_| AuthError::InvalidToken)?;
+        let signature = base64::decode(sig_b64).map_err(|_| AuthError::InvalidToken)?;
+
+        let mut mac = HmacSha256::new_from_slice(&self.private_key).expect("key of any length");
+        mac.update(&payload);
+        mac.verify_slice(&signature)
+            .map_err(|_| AuthError::InvalidToken)?;
+
+        Ok(payload)
+    }
+
+    /// Generates a signed token granting `authorization` access to `doc_id`,
+    /// expiring at the given absolute `expiration_millis`.
+    pub fn gen_doc_token(
+        &self,
+        doc_id: &str,
+        authorization: Authorization,
+        expiration_millis: u64,
+    ) -> String {
+        let payload = DocPayload {
+            doc_id: doc_id.to_string(),
+            authorization,
+            expiration_millis,
+        };
+        let payload = serde_json::to_vec(&payload).expect("payload is serializable");
+        self.sign(&payload)
+    }
+
+    /// Verifies a document token, returning the authorization level it was
+    /// issued with.
+    pub fn verify_doc_token(
+        &self,
+        token: &str,
+        doc_id: &str,
+        current_time_millis: u64,
+    ) -> Result<Authorization, AuthError> {
+        self.verify_doc_token_with_grace(token, doc_id, current_time_millis, 0)
+    }
+
+    /// Like [`Self::verify_doc_token`], but allows a token that expired up to
+    /// `grace_millis` ago to still verify successfully. Used by the token
+    /// refresh endpoint, which needs to accept a token whose expiry a client
+    /// only noticed after the fact.
+    pub fn verify_doc_token_with_grace(
+        &self,
+        token: &str,
+        doc_id: &str,
+        current_time_millis: u64,
+        grace_millis: u64,
+    ) -> Result<Authorization, AuthError> {
+        let payload = self.verify(token)?;
+        let payload: DocPayload =
+            serde_json::from_slice(&payload).map_err(|_| AuthError::InvalidToken)?;
+
+        if payload.doc_id != doc_id {
+            return Err(AuthError::InvalidResource);
+        }
+        if payload.expiration_millis + grace_millis < current_time_millis {
+            return Err(AuthError::Expired);
+        }
+
+        Ok(payload.authorization)
+    }
+
+    pub fn gen_server_token(&self, current_time_millis: u64) -> String {
+        let payload = ServerPayload {
+            expiration_millis: current_time_millis + DEFAULT_EXPIRATION_MILLIS,
+        };
+        let payload = serde_json::to_vec(&payload).expect("payload is serializable");
+        self.sign(&payload)
+    }
+
+    pub fn verify_server_token(
+        &self,
+        token: &str,
+        current_time_millis: u64,
+    ) -> Result<(), AuthError> {
+        let payload = self.verify(token)?;
+        let payload: ServerPayload =
+            serde_json::from_slice(&payload).map_err(|_| AuthError::InvalidToken)?;
+
+        if payload.expiration_millis < current_time_millis {
+            return Err(AuthError::Expired);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::store::Store;
+use std::cell::RefCell;
+use std::sync::Arc;
+use yrs::{Doc, Transact};
+
+/// A Yjs document paired with a KV-backed store that the document's updates
+/// are persisted into.
+pub struct DocWithSyncKv {
+    doc: Doc,
+    sync_kv: SyncKv,
+}
+
+impl DocWithSyncKv {
+    pub async fn new<F>(
+        doc_id: &str,
+        store: Option<Arc<dyn Store>>,
+        update_callback: F,
+    ) -> Result<Self, String>
+    where
+        F: Fn() + 'static,
+    {
+        let sync_kv = SyncKv::new(doc_id, store);
+        let doc = Doc::new();
+
+        if let Some(update) = sync_kv.load().await? {
+            let mut txn = doc.transact_mut();
+            yrs::updates::decoder::Decode::decode_v1(&update)
+                .map_err(|e| e.to_string())
+                .and_then(|u| txn.apply_update(u).map_err(|e| e.to_string()))?;
+        }
+
+        // Keep `sync_kv`'s in-memory snapshot of the document's encoded
+        // state up to date as updates come in. `persist()` does not read
+        // this snapshot; it encodes the live document instead.
+        let snapshot_doc = doc.clone();
+        let snapshot = sync_kv.snapshot.clone();
+        doc.observe_update_v1(move |_, _| {
+            let txn = snapshot_doc.transact();
+            *snapshot.borrow_mut() =
+                Some(txn.encode_state_as_update_v1(&yrs::StateVector::default()));
+            update_callback()
+        })
+        .map_err(|e| e.to_string())?
+        .forget();
+
+        Ok(Self { doc, sync_kv })
+    }
+
+    pub fn doc(&self) -> &Doc {
+        &self.doc
+    }
+
+    pub fn sync_kv(&self) -> &SyncKv {
+        &self.sync_kv
+    }
+
+    pub fn as_update(&self) -> Vec<u8> {
+        let txn = self.doc.transact();
+        txn.encode_state_as_update_v1(&yrs::StateVector::default())
+    }
+
+    pub fn apply_update(&self, update: &[u8]) -> Result<(), String> {
+        let mut txn = self.doc.transact_mut();
+        let update = yrs::Update::decode_v1(update).map_err(|e| e.to_string())?;
+        txn.apply_update(update).map_err(|e| e.to_string())
+    }
+
+    /// Flushes the document's current encoded state to the store. Always
+    /// writes the live state (via [`Self::as_update`]) rather than the
+    /// update-observer snapshot, so an explicit call right
+    /// after an `apply_update` (e.g. from the as-update import endpoint)
+    /// persists what was just applied even if the observer hasn't fired yet.
+    pub async fn persist(&self) -> Result<(), String> {
+        self.sync_kv.persist(self.as_update()).await
+    }
+}
+
+/// Persists a document's binary state to the configured [`Store`] under
+/// `{doc_id}/data.ysweet`.
+pub struct SyncKv {
+    doc_id: String,
+    store: Option<Arc<dyn Store>>,
+    snapshot: Arc<RefCell<Option<Vec<u8>>>>,
+}
+
+impl SyncKv {
+    fn new(doc_id: &str, store: Option<Arc<dyn Store>>) -> Self {
+        Self {
+            doc_id: doc_id.to_string(),
+            store,
+            snapshot: Arc::new(RefCell::new(None)),
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("{}/data.ysweet", self.doc_id)
+    }
+
+    async fn load(&self) -> Result<Option<Vec<u8>>, String> {
+        if let Some(store) = &self.store {
+            store.get(&self.key()).await.map_err(|e| format!("{e:?}"))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Writes `update` to the configured store under this document's key.
+    /// A no-op (but `Ok`) if no store was configured, e.g. in tests.
+    pub async fn persist(&self, update: Vec<u8>) -> Result<(), String> {
+        *self.snapshot.borrow_mut() = Some(update.clone());
+        if let Some(store) = &self.store {
+            store
+                .set(&self.key(), update)
+                .await
+                .map_err(|e| format!("{e:?}"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod api_types;
+pub mod auth;
+pub mod doc_sync;
+pub mod store;
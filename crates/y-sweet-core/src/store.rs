@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum StoreError {
+    ConnectionError(String),
+    BucketDoesNotExist(String),
+    NotAuthorized(String),
+    Other(String),
+}
+
+/// Metadata about a single key returned from [`Store::list`].
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreListEntry {
+    pub key: String,
+    pub last_modified_millis: Option<u64>,
+    pub size: Option<u64>,
+}
+
+/// A page of results from [`Store::list`], along with a cursor to continue
+/// from if the listing was truncated.
+pub struct StoreListResult {
+    pub entries: Vec<StoreListEntry>,
+    pub cursor: Option<String>,
+}
+
+#[async_trait(?Send)]
+pub trait Store {
+    async fn init(&self) -> Result<(), StoreError>;
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError>;
+    async fn set(&self, key: &str, value: Vec<u8>) -> Result<(), StoreError>;
+    async fn remove(&self, key: &str) -> Result<(), StoreError>;
+    async fn exists(&self, key: &str) -> Result<bool, StoreError>;
+
+    /// Lists up to `limit` keys under `prefix`, resuming from `cursor` if
+    /// given (as returned by a previous call).
+    async fn list(
+        &self,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<StoreListResult, StoreError>;
+}
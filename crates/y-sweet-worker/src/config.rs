@@ -0,0 +1,46 @@
+use worker::Env;
+
+const DEFAULT_TOKEN_EXPIRATION_SECONDS: u64 = 24 * 60 * 60;
+
+#[derive(Clone)]
+pub struct Configuration {
+    pub auth: Option<String>,
+    pub url_prefix: Option<String>,
+    pub bucket: String,
+    pub token_expiration_seconds: u64,
+    /// If set, the durable object POSTs a batched change notification here
+    /// whenever a document is updated.
+    pub webhook_url: Option<String>,
+    /// Shared secret used to HMAC-sign outgoing webhook payloads so
+    /// receivers can verify they came from this server.
+    pub webhook_secret: Option<String>,
+}
+
+impl TryFrom<&Env> for Configuration {
+    type Error = String;
+
+    fn try_from(env: &Env) -> Result<Self, Self::Error> {
+        let auth = env.var("AUTH_KEY").ok().map(|v| v.to_string());
+        let url_prefix = env.var("URL_PREFIX").ok().map(|v| v.to_string());
+        let bucket = env
+            .var("BUCKET_NAME")
+            .map(|v| v.to_string())
+            .map_err(|_| "Missing BUCKET_NAME".to_string())?;
+        let token_expiration_seconds = env
+            .var("TOKEN_EXPIRATION_SECONDS")
+            .ok()
+            .and_then(|v| v.to_string().parse().ok())
+            .unwrap_or(DEFAULT_TOKEN_EXPIRATION_SECONDS);
+        let webhook_url = env.var("WEBHOOK_URL").ok().map(|v| v.to_string());
+        let webhook_secret = env.var("WEBHOOK_SECRET").ok().map(|v| v.to_string());
+
+        Ok(Configuration {
+            auth,
+            url_prefix,
+            bucket,
+            token_expiration_seconds,
+            webhook_url,
+            webhook_secret,
+        })
+    }
+}
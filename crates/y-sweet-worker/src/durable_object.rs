@@ -0,0 +1,306 @@
+use crate::r2_store::R2Store;
+use crate::threadless::Threadless;
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use worker::{
+    durable_object, Env, Fetch, Headers, Method, Request, RequestInit, Response, Result, State,
+    WebSocket, WebSocketPair,
+};
+use y_sweet_core::{api_types::Authorization, doc_sync::DocWithSyncKv};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long to wait after the last change before flushing a webhook
+/// notification, so a burst of edits collapses into a single event.
+const WEBHOOK_DEBOUNCE: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Default)]
+struct WebhookConfig {
+    url: Option<String>,
+    secret: Option<String>,
+}
+
+/// A single live WebSocket connection to this document, identified so we can
+/// exclude the sender when relaying its own update back out.
+struct Session {
+    id: u64,
+    ws: WebSocket,
+}
+
+/// Returns true if a client holding `authorization` is allowed to apply a
+/// write it sent over the wire. Pulled out as a pure function so the
+/// read-only/full-access gating can be unit tested without a live
+/// WebSocket/Durable Object runtime.
+fn can_apply_update(authorization: Authorization) -> bool {
+    authorization == Authorization::Full
+}
+
+/// Returns the ids of sessions that should receive a relayed update,
+/// excluding the sender. Authorization level is irrelevant here: read-only
+/// clients still need to observe every other client's writes.
+fn broadcast_targets(session_ids: &[u64], sender_id: u64) -> Vec<u64> {
+    session_ids
+        .iter()
+        .copied()
+        .filter(|id| *id != sender_id)
+        .collect()
+}
+
+#[durable_object]
+pub struct YServe {
+    state: State,
+    env: Env,
+    doc: Rc<RefCell<Option<Threadless<DocWithSyncKv>>>>,
+    webhook: Rc<RefCell<WebhookConfig>>,
+    pending_changes: Rc<Cell<u32>>,
+    sessions: Rc<RefCell<Vec<Session>>>,
+    next_session_id: Cell<u64>,
+}
+
+// Safety: same rationale as `Threadless` - Cloudflare Workers run this
+// Durable Object on a single thread, so the `Rc`/`RefCell`/`Cell` fields
+// above are never actually touched concurrently even though the runtime's
+// trait bounds require `Send`.
+unsafe impl Send for YServe {}
+
+impl DurableObject for YServe {
+    fn new(state: State, env: Env) -> Self {
+        Self {
+            state,
+            env,
+            doc: Rc::new(RefCell::new(None)),
+            webhook: Rc::new(RefCell::new(WebhookConfig::default())),
+            pending_changes: Rc::new(Cell::new(0)),
+            sessions: Rc::new(RefCell::new(Vec::new())),
+            next_session_id: Cell::new(0),
+        }
+    }
+
+    async fn fetch(&mut self, req: Request) -> Result<Response> {
+        let doc_id = self.state.id().to_string();
+
+        let authorization = req
+            .headers()
+            .get("X-Ysweet-Authorization")?
+            .and_then(|h| match h.as_str() {
+                "read-only" => Some(Authorization::ReadOnly),
+                "full" => Some(Authorization::Full),
+                _ => None,
+            })
+            .unwrap_or(Authorization::Full);
+
+        *self.webhook.borrow_mut() = WebhookConfig {
+            url: req.headers().get("X-Ysweet-Webhook-Url")?,
+            secret: req.headers().get("X-Ysweet-Webhook-Secret")?,
+        };
+
+        if self.doc.borrow().is_none() {
+            // The bucket binding name is forwarded from `ServerContext` on
+            // every request so the DO can write the same R2 bucket the HTTP
+            // handlers read from, rather than losing writes by running with
+            // no store at all.
+            let bucket_name = req
+                .headers()
+                .get("X-Ysweet-Bucket")?
+                .ok_or_else(|| worker::Error::RustError("missing X-Ysweet-Bucket".to_string()))?;
+            let store: Arc<dyn y_sweet_core::store::Store> =
+                Arc::new(R2Store::new(&self.env, &bucket_name));
+
+            let dwskv = DocWithSyncKv::new(&doc_id, Some(store), || {})
+                .await
+                .map_err(worker::Error::RustError)?;
+            *self.doc.borrow_mut() = Some(Threadless::new(dwskv));
+        }
+
+        let pair = WebSocketPair::new()?;
+        let server = pair.server;
+        server.accept()?;
+
+        self.handle_session(server, authorization);
+
+        Response::from_websocket(pair.client)
+    }
+
+    async fn alarm(&mut self) -> Result<Response> {
+        let change_count = self.pending_changes.replace(0);
+        if change_count > 0 {
+            if let Err(e) = self.send_webhook(change_count).await {
+                worker::console_log!("failed to send webhook: {e}");
+            }
+        }
+        Response::empty()
+    }
+}
+
+impl YServe {
+    fn handle_session(&self, ws: WebSocket, authorization: Authorization) {
+        let session_id = self.next_session_id.get();
+        self.next_session_id.set(session_id + 1);
+
+        {
+            let doc = self.doc.borrow();
+            let doc = doc.as_ref().expect("doc is initialized above");
+            // Send the current state to the newly connected client
+            // regardless of its authorization level; only incoming writes
+            // are gated.
+            let _ = ws.send_with_bytes(doc.as_update());
+        }
+
+        self.sessions.borrow_mut().push(Session {
+            id: session_id,
+            ws: ws.clone(),
+        });
+
+        // Everything captured here must be owned (not borrowed from `self`)
+        // since this task outlives the synchronous call that spawns it.
+        let doc = self.doc.clone();
+        let sessions = self.sessions.clone();
+        let pending_changes = self.pending_changes.clone();
+        let storage = self.state.storage();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let mut events = ws.events().expect("could not open stream");
+            use futures_util::StreamExt;
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(worker::WebsocketEvent::Message(msg)) => {
+                        let Some(bytes) = msg.bytes() else {
+                            continue;
+                        };
+
+                        if !can_apply_update(authorization) {
+                            // Read-only connections may not mutate the
+                            // document; silently drop the update rather
+                            // than tearing down the socket, so the client
+                            // keeps receiving changes.
+                            continue;
+                        }
+
+                        let applied = {
+                            let doc_ref = doc.borrow();
+                            let doc_ref = doc_ref.as_ref().expect("doc is initialized above");
+                            match doc_ref.apply_update(&bytes) {
+                                Ok(()) => {
+                                    // Flush to the store immediately so
+                                    // `exists`/`as-update`/`list` HTTP
+                                    // endpoints observe the edit even if no
+                                    // one ever reconnects to this durable
+                                    // object again. Only count the change
+                                    // toward the webhook if it actually
+                                    // landed - otherwise we'd notify
+                                    // subscribers about an edit that
+                                    // `as-update` can never show them.
+                                    doc_ref.persist().await.is_ok()
+                                }
+                                Err(e) => {
+                                    worker::console_log!("failed to apply update: {e}");
+                                    false
+                                }
+                            }
+                        };
+
+                        if !applied {
+                            continue;
+                        }
+
+                        // Relay the raw update to every other live session
+                        // for this doc so concurrent editors see each
+                        // other's changes without reconnecting.
+                        let ids: Vec<u64> =
+                            sessions.borrow().iter().map(|s| s.id).collect();
+                        for target_id in broadcast_targets(&ids, session_id) {
+                            if let Some(session) = sessions
+                                .borrow()
+                                .iter()
+                                .find(|s| s.id == target_id)
+                            {
+                                let _ = session.ws.send_with_bytes(&bytes);
+                            }
+                        }
+
+                        pending_changes.set(pending_changes.get() + 1);
+                        // Push the alarm out on every change so a burst of
+                        // edits is reported as a single debounced event.
+                        let _ = storage.set_alarm(WEBHOOK_DEBOUNCE).await;
+                    }
+                    Ok(worker::WebsocketEvent::Close(_)) => break,
+                    Err(_) => break,
+                }
+            }
+
+            sessions.borrow_mut().retain(|s| s.id != session_id);
+        });
+    }
+
+    async fn send_webhook(&self, change_count: u32) -> Result<()> {
+        let webhook = self.webhook.borrow().clone();
+        let Some(url) = webhook.url else {
+            return Ok(());
+        };
+
+        let doc_id = self.state.id().to_string();
+        let body = json!({
+            "doc_id": doc_id,
+            "timestamp": crate::get_time_millis_since_epoch(),
+            "change_count": change_count,
+        })
+        .to_string();
+
+        let mut headers = Headers::new();
+        headers.set("Content-Type", "application/json")?;
+        if let Some(secret) = webhook.secret {
+            let mut mac =
+                HmacSha256::new_from_slice(secret.as_bytes()).expect("key of any length");
+            mac.update(body.as_bytes());
+            let signature = hex::encode(mac.finalize().into_bytes());
+            headers.set("X-Ysweet-Signature", &signature)?;
+        }
+
+        let mut init = RequestInit::new();
+        init.with_method(Method::Post)
+            .with_headers(headers)
+            .with_body(Some(body.into()));
+
+        let req = Request::new_with_init(&url, &init)?;
+        Fetch::Request(req).send().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_cannot_apply_updates() {
+        assert!(!can_apply_update(Authorization::ReadOnly));
+    }
+
+    #[test]
+    fn full_access_can_apply_updates() {
+        assert!(can_apply_update(Authorization::Full));
+    }
+
+    #[test]
+    fn broadcast_excludes_sender_but_includes_everyone_else() {
+        let sessions = vec![1, 2, 3];
+        let mut targets = broadcast_targets(&sessions, 2);
+        targets.sort();
+        assert_eq!(targets, vec![1, 3]);
+    }
+
+    #[test]
+    fn broadcast_includes_read_only_sessions() {
+        // Authorization only gates whether a session's own messages are
+        // applied, not whether it receives other sessions' updates - so the
+        // target list doesn't take it into account at all.
+        let sessions = vec![10, 20];
+        assert_eq!(broadcast_targets(&sessions, 10), vec![20]);
+    }
+}
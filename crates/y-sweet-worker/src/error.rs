@@ -0,0 +1,71 @@
+use serde::Serialize;
+use worker::{Response, Result};
+
+#[derive(Debug)]
+pub enum Error {
+    ExpectedAuthHeader,
+    BadAuthHeader,
+    ExpectedClientAuthHeader,
+    BadClientAuthHeader,
+    BadRequest,
+    InvalidDocName,
+    NoSuchDocument,
+    UpstreamConnectionError,
+    MissingHostHeader,
+    ConfigurationError { field: String, value: String },
+    InternalError,
+}
+
+impl Error {
+    fn status_code(&self) -> u16 {
+        match self {
+            Error::ExpectedAuthHeader
+            | Error::BadAuthHeader
+            | Error::ExpectedClientAuthHeader
+            | Error::BadClientAuthHeader => 401,
+            Error::BadRequest | Error::InvalidDocName => 400,
+            Error::NoSuchDocument => 404,
+            Error::UpstreamConnectionError => 502,
+            Error::MissingHostHeader | Error::ConfigurationError { .. } | Error::InternalError => {
+                500
+            }
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Error::ExpectedAuthHeader => "Expected an Authorization header.".to_string(),
+            Error::BadAuthHeader => "Invalid Authorization header.".to_string(),
+            Error::ExpectedClientAuthHeader => "Expected a client token.".to_string(),
+            Error::BadClientAuthHeader => "Invalid token.".to_string(),
+            Error::BadRequest => "Bad request.".to_string(),
+            Error::InvalidDocName => "Invalid document name.".to_string(),
+            Error::NoSuchDocument => "No such document.".to_string(),
+            Error::UpstreamConnectionError => "Error connecting to upstream store.".to_string(),
+            Error::MissingHostHeader => "Missing Host header.".to_string(),
+            Error::ConfigurationError { field, value } => {
+                format!("Invalid configuration for `{field}`: `{value}`.")
+            }
+            Error::InternalError => "Internal error.".to_string(),
+        }
+    }
+}
+
+impl From<Error> for Result<Response> {
+    fn from(err: Error) -> Self {
+        Response::error(err.message(), err.status_code())
+    }
+}
+
+pub trait IntoResponse {
+    fn into_response(self) -> Result<Response>;
+}
+
+impl<T: Serialize> IntoResponse for std::result::Result<T, Error> {
+    fn into_response(self) -> Result<Response> {
+        match self {
+            Ok(value) => Response::from_json(&value),
+            Err(err) => err.into(),
+        }
+    }
+}
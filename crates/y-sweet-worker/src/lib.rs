@@ -8,7 +8,7 @@ use std::collections::HashMap;
 use worker::{event, Env};
 use worker::{Date, Request, Response, Result, RouteContext, Router, Url};
 use y_sweet_core::{
-    api_types::{validate_doc_name, ClientToken, DocCreationRequest, NewDocResponse},
+    api_types::{validate_doc_name, Authorization, ClientToken, DocCreationRequest, NewDocResponse},
     auth::Authenticator,
     doc_sync::DocWithSyncKv,
     store::StoreError,
@@ -23,7 +23,7 @@ pub mod threadless;
 
 const DURABLE_OBJECT: &str = "Y_SWEET";
 
-fn get_time_millis_since_epoch() -> u64 {
+pub(crate) fn get_time_millis_since_epoch() -> u64 {
     let now = Date::now();
     now.as_millis()
 }
@@ -35,7 +35,12 @@ pub fn router(
         .get("/", |_, _| Response::ok("Y-Sweet!"))
         .get_async("/check_store", check_store_handler)
         .post_async("/doc/new", new_doc_handler)
+        .get_async("/docs", list_docs_handler)
+        .get_async("/doc/:doc_id", get_doc_handler)
         .post_async("/doc/:doc_id/auth", auth_doc_handler)
+        .post_async("/doc/:doc_id/token/refresh", refresh_doc_token_handler)
+        .get_async("/doc/:doc_id/as-update", get_as_update_handler)
+        .post_async("/doc/:doc_id/as-update", post_as_update_handler)
         .get_async("/doc/ws/:doc_id", forward_to_durable_object))
 }
 
@@ -105,13 +110,182 @@ async fn new_doc(
     let store = Some(ctx.data.store());
     let dwskv = DocWithSyncKv::new(&doc_id, store, || {}).await.unwrap();
 
-    dwskv.sync_kv().persist().await.unwrap();
+    dwskv.persist().await.unwrap();
 
     let response = NewDocResponse { doc: doc_id };
 
     Ok(response)
 }
 
+async fn get_as_update_handler(
+    req: Request,
+    ctx: RouteContext<ServerContext>,
+) -> Result<Response> {
+    match get_as_update(req, ctx).await {
+        Ok(update) => Response::from_bytes(update).map(|resp| {
+            resp.with_headers({
+                let mut headers = worker::Headers::new();
+                let _ = headers.set("Content-Type", "application/octet-stream");
+                headers
+            })
+        }),
+        Err(err) => err.into(),
+    }
+}
+
+async fn get_as_update(
+    req: Request,
+    mut ctx: RouteContext<ServerContext>,
+) -> std::result::Result<Vec<u8>, Error> {
+    check_server_token(&req, ctx.data.auth()?)?;
+
+    let doc_id = ctx.param("doc_id").unwrap().to_string();
+
+    let store = Some(ctx.data.store());
+    if !store
+        .as_ref()
+        .unwrap()
+        .exists(&format!("{doc_id}/data.ysweet"))
+        .await
+        .map_err(|_| Error::UpstreamConnectionError)?
+    {
+        return Err(Error::NoSuchDocument);
+    }
+
+    let dwskv = DocWithSyncKv::new(&doc_id, store, || {})
+        .await
+        .map_err(|_| Error::InternalError)?;
+
+    Ok(dwskv.as_update())
+}
+
+async fn post_as_update_handler(
+    req: Request,
+    ctx: RouteContext<ServerContext>,
+) -> Result<Response> {
+    post_as_update(req, ctx).await.into_response()
+}
+
+async fn post_as_update(
+    mut req: Request,
+    mut ctx: RouteContext<ServerContext>,
+) -> std::result::Result<Value, Error> {
+    check_server_token(&req, ctx.data.auth()?)?;
+
+    let doc_id = ctx.param("doc_id").unwrap().to_string();
+    if !validate_doc_name(&doc_id) {
+        return Err(Error::InvalidDocName);
+    }
+
+    let update = req
+        .bytes()
+        .await
+        .map_err(|_| Error::BadRequest)?;
+
+    let store = Some(ctx.data.store());
+    let dwskv = DocWithSyncKv::new(&doc_id, store, || {})
+        .await
+        .map_err(|_| Error::InternalError)?;
+
+    dwskv
+        .apply_update(&update)
+        .map_err(|_| Error::BadRequest)?;
+    dwskv.persist().await.map_err(|_| Error::InternalError)?;
+
+    // Check that the doc's key is present in the store after persisting.
+    // This confirms only that the key exists, not that its contents match
+    // what was just imported.
+    if !ctx
+        .data
+        .store()
+        .exists(&format!("{doc_id}/data.ysweet"))
+        .await
+        .map_err(|_| Error::UpstreamConnectionError)?
+    {
+        return Err(Error::InternalError);
+    }
+
+    Ok(json!({"doc": doc_id}))
+}
+
+async fn list_docs_handler(req: Request, ctx: RouteContext<ServerContext>) -> Result<Response> {
+    list_docs(req, ctx).await.into_response()
+}
+
+const DEFAULT_LIST_LIMIT: u32 = 100;
+const MAX_LIST_LIMIT: u32 = 1000;
+
+async fn list_docs(
+    req: Request,
+    mut ctx: RouteContext<ServerContext>,
+) -> std::result::Result<Value, Error> {
+    check_server_token(&req, ctx.data.auth()?)?;
+
+    let url = req.url().map_err(|_| Error::BadRequest)?;
+    let query: HashMap<String, String> = url
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let cursor = query.get("cursor").map(|s| s.as_str());
+    let limit = query
+        .get("limit")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    let store = ctx.data.store();
+    let result = store
+        .list("", cursor, limit)
+        .await
+        .map_err(|_| Error::UpstreamConnectionError)?;
+
+    let docs: Vec<Value> = result
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let doc_id = entry.key.strip_suffix("/data.ysweet")?;
+            Some(json!({
+                "doc_id": doc_id,
+                "last_modified_millis": entry.last_modified_millis,
+                "size": entry.size,
+            }))
+        })
+        .collect();
+
+    Ok(json!({"docs": docs, "cursor": result.cursor}))
+}
+
+async fn get_doc_handler(req: Request, ctx: RouteContext<ServerContext>) -> Result<Response> {
+    get_doc(req, ctx).await.into_response()
+}
+
+async fn get_doc(
+    req: Request,
+    mut ctx: RouteContext<ServerContext>,
+) -> std::result::Result<Value, Error> {
+    check_server_token(&req, ctx.data.auth()?)?;
+
+    let doc_id = ctx.param("doc_id").unwrap().to_string();
+
+    let store = ctx.data.store();
+    let result = store
+        .list(&format!("{doc_id}/data.ysweet"), None, 1)
+        .await
+        .map_err(|_| Error::UpstreamConnectionError)?;
+
+    let entry = result
+        .entries
+        .into_iter()
+        .find(|entry| entry.key == format!("{doc_id}/data.ysweet"))
+        .ok_or(Error::NoSuchDocument)?;
+
+    Ok(json!({
+        "doc_id": doc_id,
+        "last_modified_millis": entry.last_modified_millis,
+        "size": entry.size,
+    }))
+}
+
 async fn check_store_handler(req: Request, ctx: RouteContext<ServerContext>) -> Result<Response> {
     check_store(req, ctx).await.into_response()
 }
@@ -143,7 +317,7 @@ async fn auth_doc_handler(req: Request, ctx: RouteContext<ServerContext>) -> Res
 }
 
 async fn auth_doc(
-    req: Request,
+    mut req: Request,
     mut ctx: RouteContext<ServerContext>,
 ) -> std::result::Result<ClientToken, Error> {
     check_server_token(&req, ctx.data.auth()?)?;
@@ -159,10 +333,31 @@ async fn auth_doc(
         return Err(Error::NoSuchDocument);
     }
 
+    // The desired access level can be given in the request body or, for
+    // clients that don't send one (e.g. a plain GET-style auth check), as a
+    // query parameter. Defaults to full access for backwards compatibility.
+    let body = req.json::<DocCreationRequest>().await.ok();
+    let authorization = body
+        .and_then(|b| b.authorization)
+        .or_else(|| {
+            req.url()
+                .ok()?
+                .query_pairs()
+                .find(|(k, _)| k == "authorization")
+                .and_then(|(_, v)| match v.as_ref() {
+                    "read-only" => Some(Authorization::ReadOnly),
+                    "full" => Some(Authorization::Full),
+                    _ => None,
+                })
+        })
+        .unwrap_or_default();
+
+    let expires_at =
+        get_time_millis_since_epoch() + ctx.data.config.token_expiration_seconds * 1000;
     let token = ctx
         .data
         .auth()?
-        .map(|auth| auth.gen_doc_token(&doc_id, get_time_millis_since_epoch()));
+        .map(|auth| auth.gen_doc_token(&doc_id, authorization, expires_at));
 
     let url = if let Some(url_prefix) = &ctx.data.config.url_prefix {
         let mut parsed = Url::parse(url_prefix).map_err(|_| Error::ConfigurationError {
@@ -205,6 +400,67 @@ async fn auth_doc(
         url,
         doc: doc_id.to_string(),
         token,
+        authorization,
+        expires_at: Some(expires_at),
+    })
+}
+
+async fn refresh_doc_token_handler(
+    req: Request,
+    ctx: RouteContext<ServerContext>,
+) -> Result<Response> {
+    refresh_doc_token(req, ctx).await.into_response()
+}
+
+/// Grace period during which an already-expired token is still accepted for
+/// refresh, so a client that only notices expiry after the fact (e.g. after
+/// a dropped connection) isn't locked out.
+const REFRESH_GRACE_MILLIS: u64 = 5 * 60 * 1000;
+
+async fn refresh_doc_token(
+    mut req: Request,
+    mut ctx: RouteContext<ServerContext>,
+) -> std::result::Result<ClientToken, Error> {
+    let doc_id = ctx.param("doc_id").unwrap().to_string();
+
+    let existing = req
+        .json::<ClientToken>()
+        .await
+        .map_err(|_| Error::BadRequest)?;
+    let existing_token = existing.token.ok_or(Error::ExpectedClientAuthHeader)?;
+
+    // A valid signature alone doesn't mean the doc is still around - without
+    // this, a deleted/GC'd doc's token could be refreshed forever with no
+    // way to revoke it short of rotating the server's signing key.
+    let store = ctx.data.store();
+    if !store
+        .exists(&format!("{doc_id}/data.ysweet"))
+        .await
+        .map_err(|_| Error::UpstreamConnectionError)?
+    {
+        return Err(Error::NoSuchDocument);
+    }
+
+    let auth = ctx.data.auth()?.ok_or(Error::InternalError)?;
+    let authorization = auth
+        .verify_doc_token_with_grace(
+            &existing_token,
+            &doc_id,
+            get_time_millis_since_epoch(),
+            REFRESH_GRACE_MILLIS,
+        )
+        .map_err(|_| Error::BadClientAuthHeader)?;
+
+    let expires_at =
+        get_time_millis_since_epoch() + ctx.data.config.token_expiration_seconds * 1000;
+    let token = Some(auth.gen_doc_token(&doc_id, authorization, expires_at));
+
+    Ok(ClientToken {
+        url: existing.url,
+        doc: doc_id,
+        token,
+        authorization,
+        expires_at: Some(expires_at),
     })
 }
 
@@ -214,6 +470,8 @@ async fn forward_to_durable_object(
 ) -> Result<Response> {
     let doc_id = ctx.param("doc_id").unwrap().to_string();
 
+    let mut authorization = Authorization::Full;
+
     if let Some(auth) = ctx.data.auth().unwrap() {
         // Read query params.
         let url = req.url()?;
@@ -230,9 +488,10 @@ async fn forward_to_durable_object(
         let result = auth
             .verify_doc_token(token, &doc_id, get_time_millis_since_epoch())
             .map_err(|_| Error::BadClientAuthHeader);
-        if let Err(e) = result {
-            return e.into();
-        }
+        authorization = match result {
+            Ok(authorization) => authorization,
+            Err(e) => return e.into(),
+        };
     }
 
     let durable_object = ctx.env.durable_object(DURABLE_OBJECT)?;
@@ -244,6 +503,13 @@ async fn forward_to_durable_object(
     *req.path_mut()? = path; // Cloning does not clone path (maybe a workers-rs bug?)
 
     ctx.data.install_on_request(&mut req)?;
+    req.headers_mut()?.set(
+        "X-Ysweet-Authorization",
+        match authorization {
+            Authorization::ReadOnly => "read-only",
+            Authorization::Full => "full",
+        },
+    )?;
 
     stub.fetch_with_request(req).await
 }
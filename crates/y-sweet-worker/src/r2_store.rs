@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use worker::Env;
+use y_sweet_core::store::{Store, StoreError, StoreListEntry, StoreListResult};
+
+/// `Store` implementation backed by a Cloudflare R2 bucket binding.
+pub struct R2Store {
+    bucket_name: String,
+    env: Env,
+}
+
+impl R2Store {
+    pub fn new(env: &Env, bucket_name: &str) -> Self {
+        Self {
+            bucket_name: bucket_name.to_string(),
+            env: env.clone(),
+        }
+    }
+
+    fn bucket(&self) -> Result<worker::Bucket, StoreError> {
+        self.env
+            .bucket(&self.bucket_name)
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))
+    }
+}
+
+#[async_trait(?Send)]
+impl Store for R2Store {
+    async fn init(&self) -> Result<(), StoreError> {
+        self.bucket()?
+            .head("_init_check")
+            .await
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        let object = self
+            .bucket()?
+            .get(key)
+            .execute()
+            .await
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))?;
+
+        match object {
+            Some(object) => {
+                let body = object
+                    .body()
+                    .ok_or_else(|| StoreError::Other("Object has no body.".to_string()))?;
+                let bytes = body
+                    .bytes()
+                    .await
+                    .map_err(|e| StoreError::Other(e.to_string()))?;
+                Ok(Some(bytes))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>) -> Result<(), StoreError> {
+        self.bucket()?
+            .put(key, value)
+            .execute()
+            .await
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), StoreError> {
+        self.bucket()?
+            .delete(key)
+            .await
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StoreError> {
+        let head = self
+            .bucket()?
+            .head(key)
+            .await
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))?;
+        Ok(head.is_some())
+    }
+
+    async fn list(
+        &self,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<StoreListResult, StoreError> {
+        let mut options = self.bucket()?.list().prefix(prefix).limit(limit);
+        if let Some(cursor) = cursor {
+            options = options.cursor(cursor.to_string());
+        }
+
+        let listed = options
+            .execute()
+            .await
+            .map_err(|e| StoreError::ConnectionError(e.to_string()))?;
+
+        let entries = listed
+            .objects()
+            .into_iter()
+            .map(|object| StoreListEntry {
+                key: object.key(),
+                last_modified_millis: Some(object.uploaded().as_millis()),
+                size: Some(object.size() as u64),
+            })
+            .collect();
+
+        let cursor = if listed.truncated() {
+            listed.cursor()
+        } else {
+            None
+        };
+
+        Ok(StoreListResult { entries, cursor })
+    }
+}
@@ -0,0 +1,53 @@
+use crate::config::Configuration;
+use crate::error::Error;
+use crate::r2_store::R2Store;
+use std::sync::Arc;
+use worker::{Env, Request, Result};
+use y_sweet_core::{auth::Authenticator, store::Store};
+
+/// Per-request context threaded through the router and forwarded to the
+/// durable object so it has access to the same configuration.
+pub struct ServerContext {
+    pub config: Configuration,
+    auth: Option<Authenticator>,
+    store: Arc<R2Store>,
+}
+
+impl ServerContext {
+    pub fn new(config: Configuration, env: &Env) -> Self {
+        let auth = config.auth.as_deref().map(Authenticator::new);
+        let store = Arc::new(R2Store::new(env, &config.bucket));
+
+        Self {
+            config,
+            auth,
+            store,
+        }
+    }
+
+    pub fn auth(&self) -> std::result::Result<Option<&Authenticator>, Error> {
+        Ok(self.auth.as_ref())
+    }
+
+    pub fn store(&self) -> Arc<dyn Store> {
+        self.store.clone()
+    }
+
+    /// Attaches the parts of our configuration that the durable object needs
+    /// but can't derive on its own (it only sees the forwarded `Request`).
+    pub fn install_on_request(&self, req: &mut Request) -> Result<()> {
+        let mut headers = req.headers().clone();
+        if let Some(url_prefix) = &self.config.url_prefix {
+            headers.set("X-Ysweet-Url-Prefix", url_prefix)?;
+        }
+        if let Some(webhook_url) = &self.config.webhook_url {
+            headers.set("X-Ysweet-Webhook-Url", webhook_url)?;
+        }
+        if let Some(webhook_secret) = &self.config.webhook_secret {
+            headers.set("X-Ysweet-Webhook-Secret", webhook_secret)?;
+        }
+        headers.set("X-Ysweet-Bucket", &self.config.bucket)?;
+        *req.headers_mut()? = headers;
+        Ok(())
+    }
+}
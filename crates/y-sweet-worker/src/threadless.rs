@@ -0,0 +1,35 @@
+use std::ops::{Deref, DerefMut};
+
+/// Wraps a value that isn't `Send`/`Sync` so it can live inside a Durable
+/// Object's state. This is sound because Cloudflare Workers are
+/// single-threaded: nothing actually moves these values across threads.
+pub struct Threadless<T>(T);
+
+impl<T> Threadless<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Threadless<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Threadless<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// Safety: Cloudflare Workers execute on a single thread, so a value is never
+// actually accessed concurrently despite crossing an `await` point.
+unsafe impl<T> Send for Threadless<T> {}
+unsafe impl<T> Sync for Threadless<T> {}